@@ -0,0 +1,787 @@
+//! OpenTelemetry middleware.
+//!
+//! # Example
+//!
+//! TODO
+
+// TODO(david): jaeger example
+
+mod semconv;
+mod versions;
+
+use self::versions::{otel, otel_http, tracing_otel};
+use axum::{
+    extract::{ConnectInfo, MatchedPath, OriginalUri},
+    response::Response,
+};
+use bytes::Buf;
+use http::{header, uri::Scheme, Method, Request, Version};
+use otel::trace::TraceContextExt;
+use std::{borrow::Cow, net::SocketAddr, time::Duration};
+use tower_http::{
+    classify::{ServerErrorsAsFailures, ServerErrorsFailureClass, SharedClassifier},
+    request_id::RequestId,
+    trace::{MakeSpan, OnBodyChunk, OnEos, OnFailure, OnRequest, OnResponse, TraceLayer},
+};
+use tracing::{field::Empty, Span};
+use tracing_otel::OpenTelemetrySpanExt;
+
+/// OpenTelemetry tracing middleware.
+///
+/// It will use [OpenTelemetry's conventional field names][otel].
+///
+/// Use [`opentelemetry_tracing_layer_with`] instead if you need to add or override the fields
+/// recorded on the span.
+///
+/// See the [module docs](self) for more details.
+///
+/// [otel]: https://github.com/open-telemetry/opentelemetry-specification/blob/main/specification/trace/semantic_conventions/http.md
+pub fn opentelemtry_tracing_layer() -> TraceLayer<
+    SharedClassifier<ServerErrorsAsFailures>,
+    OtelMakeSpan<DefaultOtelSpanBackend>,
+    OtelOnRequest,
+    OtelOnResponse<DefaultOtelSpanBackend>,
+    OtelOnBodyChunk,
+    OtelOnEos,
+    OtelOnFailure<DefaultOtelSpanBackend>,
+> {
+    opentelemetry_tracing_layer_with(DefaultOtelSpanBackend)
+}
+
+/// Like [`opentelemtry_tracing_layer`] but lets you plug in your own [`OtelSpanBackend`] to
+/// add, remove or override the fields recorded on the span.
+///
+/// Unlike [`opentelemtry_tracing_layer`], which always uses [`DefaultOtelSpanBackend`], this
+/// takes a backend *instance*, so the backend can carry its own runtime configuration (a tenant
+/// lookup table, a build version, ...) instead of being limited to stateless marker types.
+///
+/// ```ignore
+/// opentelemetry_tracing_layer_with(MyOtelSpanBackend::new(build_version))
+/// ```
+pub fn opentelemetry_tracing_layer_with<Backend>(
+    backend: Backend,
+) -> TraceLayer<
+    SharedClassifier<ServerErrorsAsFailures>,
+    OtelMakeSpan<Backend>,
+    OtelOnRequest,
+    OtelOnResponse<Backend>,
+    OtelOnBodyChunk,
+    OtelOnEos,
+    OtelOnFailure<Backend>,
+>
+where
+    Backend: Clone,
+{
+    TraceLayer::new_for_http()
+        .make_span_with(OtelMakeSpan::new(backend.clone()))
+        .on_request(OtelOnRequest)
+        .on_response(OtelOnResponse::new(backend.clone()))
+        .on_body_chunk(OtelOnBodyChunk::default())
+        .on_eos(OtelOnEos)
+        .on_failure(OtelOnFailure::new(backend))
+}
+
+/// Customization point for the span [`tower_http::trace::Trace`] creates for an incoming
+/// request.
+///
+/// Implement this to add a custom attribute (tenant id, route group, build version, ...)
+/// without forking this module: call [`tracing::info_span!`] with your own fields plus, if you
+/// still want them, the ones [`DefaultOtelSpanBackend`] records. This is the same extension
+/// point `ReqwestOtelSpanBackend` provides for `reqwest-tracing`. Methods take `&self` so a
+/// backend can hold its own configuration, populated once and reused across every request.
+///
+/// This is generic over the request body type rather than bundling response handling too (see
+/// [`OtelResponseSpanBackend`]), since [`TraceLayer::new_for_http`] instantiates
+/// [`OtelMakeSpan`]/[`OtelOnResponse`] over the request and response body types respectively —
+/// which are usually different — so a backend that only cares about one of them doesn't have to
+/// implement a blanket impl across every body type to satisfy the other.
+pub trait OtelSpanBackend<B> {
+    /// Create the span for an incoming request.
+    fn on_request(&self, request: &Request<B>) -> Span;
+
+    /// Compute the operation name recorded in the span's `otel.name` field, which
+    /// `tracing-opentelemetry` uses to rename the exported OTel span.
+    ///
+    /// Defaults to `"HTTP request"` for every route, which is why backends that group by span
+    /// name (Datadog, Jaeger, ...) see a single operation for the whole service. Override this
+    /// — e.g. to `format!("{} {}", request.method(), matched_path).into()` using
+    /// [`MatchedPath`] — to get one operation per route instead. Use
+    /// [`DefaultOtelSpanBackend::with_operation_name`] if that's the only thing you want to
+    /// change.
+    fn name(&self, _request: &Request<B>) -> Cow<'static, str> {
+        Cow::Borrowed("HTTP request")
+    }
+}
+
+/// Customization point for the fields [`tower_http::trace::Trace`] records on the span once the
+/// response is available.
+///
+/// Split out from [`OtelSpanBackend`] because [`OtelOnResponse`] is instantiated over the
+/// response body type, which differs from the request body type [`OtelMakeSpan`] uses.
+pub trait OtelResponseSpanBackend<B> {
+    /// Record fields on the span once the response is available.
+    fn on_response(&self, response: &Response<B>, latency: Duration, span: &Span);
+}
+
+/// The part of span recording that doesn't depend on either body type, so [`OtelOnFailure`] can
+/// delegate to it without being generic over a request or response body.
+pub trait OtelFailureSpanBackend {
+    /// Record the failure outcome on the span when a response or end-of-stream is classified
+    /// as a failure.
+    fn on_failure(&self, failure: ServerErrorsFailureClass, latency: Duration, span: &Span);
+}
+
+/// The backend used by [`opentelemtry_tracing_layer`], recording the fields this module has
+/// always recorded.
+///
+/// Use [`DefaultOtelSpanBackend::with_operation_name`] to override just the `otel.name` field
+/// without reimplementing [`OtelSpanBackend`] from scratch.
+///
+/// [otel]: https://github.com/open-telemetry/opentelemetry-specification/blob/main/specification/trace/semantic_conventions/http.md
+#[derive(Clone, Copy, Debug)]
+pub struct DefaultOtelSpanBackend;
+
+impl DefaultOtelSpanBackend {
+    /// Like [`DefaultOtelSpanBackend`], but computing the span's operation name from `name_fn`
+    /// instead of the fixed `"HTTP request"` default.
+    ///
+    /// ```ignore
+    /// opentelemetry_tracing_layer_with(DefaultOtelSpanBackend::with_operation_name(
+    ///     |req: &Request<Body>| format!("{} {}", req.method(), req.uri().path()).into(),
+    /// ))
+    /// ```
+    pub fn with_operation_name<F>(name_fn: F) -> WithOperationName<F> {
+        WithOperationName { name_fn }
+    }
+}
+
+impl<B> OtelSpanBackend<B> for DefaultOtelSpanBackend {
+    fn on_request(&self, req: &Request<B>) -> Span {
+        build_request_span(req, self.name(req))
+    }
+}
+
+/// [`DefaultOtelSpanBackend`], but with the operation name computed by `name_fn` instead of the
+/// fixed `"HTTP request"` default.
+///
+/// Constructed by [`DefaultOtelSpanBackend::with_operation_name`].
+pub struct WithOperationName<F> {
+    name_fn: F,
+}
+
+impl<F: Clone> Clone for WithOperationName<F> {
+    fn clone(&self) -> Self {
+        Self {
+            name_fn: self.name_fn.clone(),
+        }
+    }
+}
+
+impl<F> std::fmt::Debug for WithOperationName<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WithOperationName").finish()
+    }
+}
+
+impl<B, F> OtelSpanBackend<B> for WithOperationName<F>
+where
+    F: Fn(&Request<B>) -> Cow<'static, str>,
+{
+    fn on_request(&self, req: &Request<B>) -> Span {
+        build_request_span(req, (self.name_fn)(req))
+    }
+
+    fn name(&self, req: &Request<B>) -> Cow<'static, str> {
+        (self.name_fn)(req)
+    }
+}
+
+impl<B, F> OtelResponseSpanBackend<B> for WithOperationName<F> {
+    fn on_response(&self, response: &Response<B>, latency: Duration, span: &Span) {
+        record_response_fields(response, latency, span)
+    }
+}
+
+impl<F> OtelFailureSpanBackend for WithOperationName<F> {
+    fn on_failure(&self, failure: ServerErrorsFailureClass, latency: Duration, span: &Span) {
+        record_failure(failure, latency, span)
+    }
+}
+
+fn build_request_span<B>(req: &Request<B>, operation_name: Cow<'static, str>) -> Span {
+    let user_agent = req
+        .headers()
+        .get(header::USER_AGENT)
+        .map_or("", |h| h.to_str().unwrap_or(""));
+
+    let host = req
+        .headers()
+        .get(header::HOST)
+        .map_or("", |h| h.to_str().unwrap_or(""));
+
+    let scheme = req
+        .uri()
+        .scheme()
+        .map_or_else(|| "HTTP".into(), http_scheme);
+
+    let http_route = if let Some(matched_path) = req.extensions().get::<MatchedPath>() {
+        matched_path.as_str().to_owned()
+    } else if let Some(uri) = req.extensions().get::<OriginalUri>() {
+        uri.0.path().to_owned()
+    } else {
+        req.uri().path().to_owned()
+    };
+
+    #[cfg(not(feature = "semconv_stable_http"))]
+    let http_target = if let Some(uri) = req.extensions().get::<OriginalUri>() {
+        uri.0.path().to_owned()
+    } else {
+        req.uri().path().to_owned()
+    };
+
+    // TODO(david): document that `into_make_service_with_connect_info` is required
+    let client_ip = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(client_ip)| Cow::from(client_ip.to_string()))
+        .unwrap_or_default();
+
+    // TODO(david): document that you have to add a request id middleware as well
+    let request_id = req
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .unwrap_or_default();
+
+    let remote_context = extract_remote_context(req.headers());
+    let remote_span = remote_context.span();
+    let span_context = remote_span.span_context();
+    let trace_id = span_context
+        .is_valid()
+        .then(|| Cow::from(span_context.trace_id().to_string()))
+        .unwrap_or_default();
+
+    // Field keys in `info_span!` must be literals, so these can't be swapped for the
+    // `semconv::legacy`/`semconv::stable` constants directly — but their text matches those
+    // constants' values. The values are set inline here (rather than via a post-creation
+    // `span.record`) so they're present on the span-`NEW` event, not just on `CLOSE`.
+    #[cfg(not(feature = "semconv_stable_http"))]
+    let span = tracing::info_span!(
+        "HTTP request",
+        http.client_ip = %client_ip,
+        http.flavor = %http_flavor(req.version()),
+        http.host = %host,
+        http.method = %http_method(req.method()),
+        http.request_content_length = Empty,
+        http.response_content_length = Empty,
+        http.route = %http_route,
+        http.scheme = %scheme,
+        http.status_code = Empty,
+        http.target = %http_target,
+        http.user_agent = %user_agent,
+        otel.kind = "server",
+        otel.name = %operation_name,
+        otel.status_code = Empty,
+        request_id = request_id,
+        trace_id = %trace_id,
+    );
+
+    #[cfg(feature = "semconv_stable_http")]
+    let span = tracing::info_span!(
+        "HTTP request",
+        client.address = %client_ip,
+        http.flavor = %http_flavor(req.version()),
+        http.host = %host,
+        http.request.method = %http_method(req.method()),
+        http.request.body.size = Empty,
+        http.response.status_code = Empty,
+        http.response.body.size = Empty,
+        http.route = %http_route,
+        http.user_agent = %user_agent,
+        otel.kind = "server",
+        otel.name = %operation_name,
+        otel.status_code = Empty,
+        request_id = request_id,
+        trace_id = %trace_id,
+        url.scheme = %scheme,
+    );
+
+    span.set_parent(remote_context);
+
+    span
+}
+
+impl<B> OtelResponseSpanBackend<B> for DefaultOtelSpanBackend {
+    fn on_response(&self, response: &Response<B>, latency: Duration, span: &Span) {
+        record_response_fields(response, latency, span)
+    }
+}
+
+fn record_response_fields<B>(response: &Response<B>, _latency: Duration, span: &Span) {
+    let status = response.status().as_u16().to_string();
+
+    #[cfg(not(feature = "semconv_stable_http"))]
+    span.record(
+        semconv::legacy::HTTP_STATUS_CODE,
+        &tracing::field::display(status),
+    );
+    #[cfg(feature = "semconv_stable_http")]
+    span.record(
+        semconv::stable::HTTP_RESPONSE_STATUS_CODE,
+        &tracing::field::display(status),
+    );
+
+    // Covers responses with a known, fixed length up front. `OtelOnBodyChunk` overwrites
+    // this with the streamed byte total on every body chunk, which is how responses that
+    // don't set `Content-Length` get a size.
+    if let Some(content_length) = content_length_header(response.headers()) {
+        span.record(
+            semconv::HTTP_RESPONSE_BODY_SIZE,
+            &tracing::field::display(content_length),
+        );
+    }
+
+    // assume there is no error, if there is `OtelOnFailure` will be called and override this
+    span.record("otel.status_code", &"OK");
+}
+
+impl OtelFailureSpanBackend for DefaultOtelSpanBackend {
+    fn on_failure(&self, failure: ServerErrorsFailureClass, latency: Duration, span: &Span) {
+        record_failure(failure, latency, span)
+    }
+}
+
+fn record_failure(failure: ServerErrorsFailureClass, _latency: Duration, span: &Span) {
+    match failure {
+        ServerErrorsFailureClass::StatusCode(status) => {
+            if status.is_server_error() {
+                span.record("otel.status_code", &"ERROR");
+            }
+        }
+        ServerErrorsFailureClass::Error(_) => {
+            span.record("otel.status_code", &"ERROR");
+        }
+    }
+}
+
+fn content_length_header(headers: &http::HeaderMap) -> Option<u64> {
+    headers
+        .get(header::CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+/// A [`MakeSpan`] that delegates to an [`OtelSpanBackend`] to create tracing spans using
+/// [OpenTelemetry's conventional field names][otel].
+///
+/// [otel]: https://github.com/open-telemetry/opentelemetry-specification/blob/main/specification/trace/semantic_conventions/http.md
+pub struct OtelMakeSpan<Backend> {
+    backend: Backend,
+}
+
+impl<Backend> OtelMakeSpan<Backend> {
+    fn new(backend: Backend) -> Self {
+        Self { backend }
+    }
+}
+
+impl<Backend: Clone> Clone for OtelMakeSpan<Backend> {
+    fn clone(&self) -> Self {
+        Self {
+            backend: self.backend.clone(),
+        }
+    }
+}
+
+impl<Backend> std::fmt::Debug for OtelMakeSpan<Backend> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OtelMakeSpan").finish()
+    }
+}
+
+impl<B, Backend> MakeSpan<B> for OtelMakeSpan<Backend>
+where
+    Backend: OtelSpanBackend<B>,
+{
+    fn make_span(&mut self, req: &Request<B>) -> Span {
+        self.backend.on_request(req)
+    }
+}
+
+fn http_method(method: &Method) -> Cow<'static, str> {
+    match method {
+        &Method::CONNECT => "CONNECT".into(),
+        &Method::DELETE => "DELETE".into(),
+        &Method::GET => "GET".into(),
+        &Method::HEAD => "HEAD".into(),
+        &Method::OPTIONS => "OPTIONS".into(),
+        &Method::PATCH => "PATCH".into(),
+        &Method::POST => "POST".into(),
+        &Method::PUT => "PUT".into(),
+        &Method::TRACE => "TRACE".into(),
+        other => other.to_string().into(),
+    }
+}
+
+fn http_flavor(version: Version) -> Cow<'static, str> {
+    match version {
+        Version::HTTP_09 => "0.9".into(),
+        Version::HTTP_10 => "1.0".into(),
+        Version::HTTP_11 => "1.1".into(),
+        Version::HTTP_2 => "2.0".into(),
+        Version::HTTP_3 => "3.0".into(),
+        other => format!("{:?}", other).into(),
+    }
+}
+
+fn http_scheme(scheme: &Scheme) -> Cow<'static, str> {
+    if scheme == &Scheme::HTTP {
+        "http".into()
+    } else if scheme == &Scheme::HTTPS {
+        "https".into()
+    } else {
+        scheme.to_string().into()
+    }
+}
+
+// If remote request has no span data the propagator defaults to an unsampled context
+fn extract_remote_context(headers: &http::HeaderMap) -> otel::Context {
+    let extractor = otel_http::HeaderExtractor(headers);
+    otel::global::get_text_map_propagator(|propagator| propagator.extract(&extractor))
+}
+
+/// Callback that [`Trace`] will call when it receives a request.
+///
+/// [`Trace`]: tower_http::trace::Trace
+#[derive(Clone, Copy, Debug)]
+pub struct OtelOnRequest;
+
+impl<B> OnRequest<B> for OtelOnRequest {
+    fn on_request(&mut self, request: &Request<B>, span: &Span) {
+        if let Some(content_length) = content_length_header(request.headers()) {
+            span.record(
+                semconv::HTTP_REQUEST_BODY_SIZE,
+                &tracing::field::display(content_length),
+            );
+        }
+    }
+}
+
+/// Callback that [`Trace`] will call when it receives a response, delegating to an
+/// [`OtelResponseSpanBackend`].
+///
+/// [`Trace`]: tower_http::trace::Trace
+pub struct OtelOnResponse<Backend> {
+    backend: Backend,
+}
+
+impl<Backend> OtelOnResponse<Backend> {
+    fn new(backend: Backend) -> Self {
+        Self { backend }
+    }
+}
+
+impl<Backend: Clone> Clone for OtelOnResponse<Backend> {
+    fn clone(&self) -> Self {
+        Self {
+            backend: self.backend.clone(),
+        }
+    }
+}
+
+impl<Backend> std::fmt::Debug for OtelOnResponse<Backend> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OtelOnResponse").finish()
+    }
+}
+
+impl<B, Backend> OnResponse<B> for OtelOnResponse<Backend>
+where
+    Backend: OtelResponseSpanBackend<B>,
+{
+    fn on_response(self, response: &Response<B>, latency: Duration, span: &Span) {
+        self.backend.on_response(response, latency, span);
+    }
+}
+
+/// Callback that [`Trace`] will call when the response body produces a chunk.
+///
+/// Accumulates the streamed byte count and records it as the response body size field (see
+/// [`semconv::HTTP_RESPONSE_BODY_SIZE`]) on every chunk, so the span holds the running total for
+/// the lifetime of the response and ends up with the complete size once the body finishes
+/// streaming.
+///
+/// [`Trace`]: tower_http::trace::Trace
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OtelOnBodyChunk {
+    bytes: u64,
+}
+
+impl<B> OnBodyChunk<B> for OtelOnBodyChunk
+where
+    B: Buf,
+{
+    fn on_body_chunk(&mut self, chunk: &B, _latency: Duration, span: &Span) {
+        self.bytes += chunk.remaining() as u64;
+        span.record(
+            semconv::HTTP_RESPONSE_BODY_SIZE,
+            &tracing::field::display(self.bytes),
+        );
+    }
+}
+
+/// Callback that [`Trace`] will call when a streaming response completes.
+///
+/// [`Trace`]: tower_http::trace::Trace
+#[derive(Clone, Copy, Debug)]
+pub struct OtelOnEos;
+
+impl OnEos for OtelOnEos {
+    fn on_eos(self, trailers: Option<&http::HeaderMap>, _stream_duration: Duration, span: &Span) {
+        // Most HTTP responses never send a `Content-Length` trailer, but gRPC-style bodies
+        // sometimes do; prefer it over the chunk-accumulated total when present since it's the
+        // value the peer actually committed to.
+        if let Some(content_length) = trailers.and_then(content_length_header) {
+            span.record(
+                semconv::HTTP_RESPONSE_BODY_SIZE,
+                &tracing::field::display(content_length),
+            );
+        }
+    }
+}
+
+/// Callback that [`Trace`] will call when a response or end-of-stream is classified as a
+/// failure, delegating to an [`OtelFailureSpanBackend`].
+///
+/// [`Trace`]: tower_http::trace::Trace
+pub struct OtelOnFailure<Backend> {
+    backend: Backend,
+}
+
+impl<Backend> OtelOnFailure<Backend> {
+    fn new(backend: Backend) -> Self {
+        Self { backend }
+    }
+}
+
+impl<Backend: Clone> Clone for OtelOnFailure<Backend> {
+    fn clone(&self) -> Self {
+        Self {
+            backend: self.backend.clone(),
+        }
+    }
+}
+
+impl<Backend> std::fmt::Debug for OtelOnFailure<Backend> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OtelOnFailure").finish()
+    }
+}
+
+impl<Backend> OnFailure<ServerErrorsFailureClass> for OtelOnFailure<Backend>
+where
+    Backend: OtelFailureSpanBackend,
+{
+    fn on_failure(&mut self, failure: ServerErrorsFailureClass, latency: Duration, span: &Span) {
+        self.backend.on_failure(failure, latency, span);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_json_diff::assert_json_include;
+    use axum::{body::Body, routing::get, Router};
+    use http::{Request, StatusCode};
+    use serde_json::{json, Value};
+    use std::{
+        convert::TryInto,
+        sync::mpsc::{self, Receiver, SyncSender},
+    };
+    use tower::{Service, ServiceBuilder, ServiceExt};
+    use tower_http::request_id::SetRequestIdLayer;
+    use tracing_subscriber::{
+        fmt::{format::FmtSpan, MakeWriter},
+        util::SubscriberInitExt,
+        EnvFilter,
+    };
+
+    #[tokio::test]
+    async fn correct_fields_on_span_for_http() {
+        let svc = Router::new()
+            .route("/", get(|| async { StatusCode::OK }))
+            .route(
+                "/users/:id",
+                get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+            )
+            .layer(
+                ServiceBuilder::new()
+                    .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+                    .layer(opentelemtry_tracing_layer()),
+            );
+
+        let [(root_new, root_close), (users_id_new, users_id_close)] = spans_for_requests(
+            svc,
+            [
+                Request::builder()
+                    .header("x-request-id", "request-id")
+                    .header("user-agent", "tests")
+                    .uri("/")
+                    .body(Body::empty())
+                    .unwrap(),
+                Request::builder()
+                    .uri("/users/123")
+                    .body(Body::empty())
+                    .unwrap(),
+            ],
+        )
+        .await;
+
+        assert_json_include!(
+            actual: root_new,
+            expected: json!({
+                "fields": {
+                    "message": "new",
+                },
+                "level": "INFO",
+                "span": {
+                    "http.client_ip": "",
+                    "http.flavor": "1.1",
+                    "http.host": "",
+                    "http.method": "GET",
+                    "http.route": "/",
+                    "http.scheme": "HTTP",
+                    "http.target": "/",
+                    "http.user_agent": "tests",
+                    "name": "HTTP request",
+                    "otel.kind": "server",
+                    "request_id": "request-id",
+                    "trace_id": ""
+                }
+            }),
+        );
+
+        assert_json_include!(
+            actual: root_close,
+            expected: json!({
+                "fields": {
+                    "message": "close",
+                },
+                "level": "INFO",
+                "span": {
+                    "http.client_ip": "",
+                    "http.flavor": "1.1",
+                    "http.host": "",
+                    "http.method": "GET",
+                    "http.route": "/",
+                    "http.scheme": "HTTP",
+                    "http.status_code": "200",
+                    "http.target": "/",
+                    "http.user_agent": "tests",
+                    "name": "HTTP request",
+                    "otel.kind": "server",
+                    "otel.status_code": "OK",
+                    "request_id": "request-id",
+                    "trace_id": ""
+                }
+            }),
+        );
+
+        assert_json_include!(
+            actual: users_id_new,
+            expected: json!({
+                "span": {
+                    "http.route": "/users/:id",
+                    "http.target": "/users/123",
+                }
+            }),
+        );
+
+        assert_json_include!(
+            actual: users_id_close,
+            expected: json!({
+                "span": {
+                    "http.status_code": "500",
+                    "otel.status_code": "ERROR",
+                }
+            }),
+        );
+    }
+
+    async fn spans_for_requests<const N: usize>(
+        mut router: Router<Body>,
+        reqs: [Request<Body>; N],
+    ) -> [(Value, Value); N] {
+        use http_body::Body as _;
+
+        let (make_writer, rx) = duplex_writer();
+        let subscriber = tracing_subscriber::fmt::fmt()
+            .json()
+            .with_env_filter(EnvFilter::try_new("axum_extra=trace").unwrap())
+            .with_writer(make_writer)
+            .with_span_events(FmtSpan::NEW | FmtSpan::CLOSE)
+            .finish();
+        let _guard = subscriber.set_default();
+
+        let mut spans = Vec::new();
+
+        for req in reqs {
+            let mut res = router.ready().await.unwrap().call(req).await.unwrap();
+
+            while res.data().await.is_some() {}
+            res.trailers().await.unwrap();
+            drop(res);
+
+            let logs = std::iter::from_fn(|| rx.try_recv().ok())
+                .map(|bytes| serde_json::from_slice::<Value>(&bytes).unwrap())
+                .collect::<Vec<_>>();
+
+            let [new, close]: [_; 2] = logs.try_into().unwrap();
+
+            spans.push((new, close));
+        }
+
+        spans.try_into().unwrap()
+    }
+
+    fn duplex_writer() -> (DuplexWriter, Receiver<Vec<u8>>) {
+        let (tx, rx) = mpsc::sync_channel(1024);
+        (DuplexWriter { tx }, rx)
+    }
+
+    #[derive(Clone)]
+    struct DuplexWriter {
+        tx: SyncSender<Vec<u8>>,
+    }
+
+    impl<'a> MakeWriter<'a> for DuplexWriter {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    impl std::io::Write for DuplexWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.tx.send(buf.to_vec()).unwrap();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    struct MakeRequestUuid;
+
+    impl tower_http::request_id::MakeRequestId for MakeRequestUuid {
+        fn make_request_id<B>(&mut self, _: &Request<B>) -> Option<RequestId> {
+            let request_id = uuid::Uuid::new_v4().to_string().parse().ok()?;
+            Some(RequestId::new(request_id))
+        }
+    }
+}
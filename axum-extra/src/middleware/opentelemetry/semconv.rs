@@ -0,0 +1,95 @@
+//! HTTP attribute key constants sourced from [`opentelemetry_semantic_conventions`], so the
+//! fields [`DefaultOtelSpanBackend`](super::DefaultOtelSpanBackend) records stay aligned with
+//! the specification instead of drifting out of sync with hand-rolled string literals. This
+//! mirrors how `dd-trace-layer` pulls its attribute keys from
+//! `opentelemetry_semantic_conventions::trace`.
+//!
+//! `info_span!` requires its field keys to be literals, so most of the per-request attributes
+//! are still spelled out as literals at span-creation time (see `build_request_span`) rather
+//! than referencing these constants directly. The literals named in the compile-time equality
+//! assertions below are tied to their semconv constant, so a `opentelemetry-semantic-conventions`
+//! bump that renames one of those keys fails the build instead of silently drifting out of sync.
+//! The other literals (`http.flavor`, `http.host`, `http.user_agent`, the status code keys and
+//! the legacy body-size keys) are not checked. Only the fields recorded later, once their value
+//! is known, reference these constants directly.
+//!
+//! [`legacy`] is used unless the `semconv_stable_http` feature is enabled, in which case
+//! [`stable`] is used instead so traces adopt the current HTTP semantic conventions.
+
+use opentelemetry_semantic_conventions::trace;
+
+/// Compile-time `&str` equality, since `str::eq` isn't a `const fn`.
+const fn bytes_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+
+    true
+}
+
+/// The legacy HTTP semantic convention attribute keys this module has always emitted.
+#[cfg(not(feature = "semconv_stable_http"))]
+pub(crate) mod legacy {
+    use super::{bytes_eq, trace};
+
+    pub(crate) const HTTP_STATUS_CODE: &str = trace::HTTP_STATUS_CODE;
+
+    // Ties the `info_span!` literals in `build_request_span` to the semconv constants they're
+    // meant to track.
+    const _: () = assert!(bytes_eq("http.client_ip", trace::HTTP_CLIENT_IP));
+    const _: () = assert!(bytes_eq("http.method", trace::HTTP_METHOD));
+    const _: () = assert!(bytes_eq("http.route", trace::HTTP_ROUTE));
+    const _: () = assert!(bytes_eq("http.scheme", trace::HTTP_SCHEME));
+    const _: () = assert!(bytes_eq("http.target", trace::HTTP_TARGET));
+}
+
+/// The current stable HTTP semantic convention attribute keys, used when `semconv_stable_http`
+/// is enabled.
+#[cfg(feature = "semconv_stable_http")]
+pub(crate) mod stable {
+    use super::{bytes_eq, trace};
+
+    pub(crate) const HTTP_RESPONSE_STATUS_CODE: &str = trace::HTTP_RESPONSE_STATUS_CODE;
+    pub(crate) const HTTP_REQUEST_BODY_SIZE: &str = trace::HTTP_REQUEST_BODY_SIZE;
+    pub(crate) const HTTP_RESPONSE_BODY_SIZE: &str = trace::HTTP_RESPONSE_BODY_SIZE;
+
+    // Ties the `info_span!` literals in `build_request_span` to the semconv constants they're
+    // meant to track.
+    const _: () = assert!(bytes_eq("client.address", trace::CLIENT_ADDRESS));
+    const _: () = assert!(bytes_eq("http.request.method", trace::HTTP_REQUEST_METHOD));
+    const _: () = assert!(bytes_eq("http.route", trace::HTTP_ROUTE));
+    const _: () = assert!(bytes_eq("url.scheme", trace::URL_SCHEME));
+    const _: () = assert!(bytes_eq(
+        "http.request.body.size",
+        trace::HTTP_REQUEST_BODY_SIZE
+    ));
+    const _: () = assert!(bytes_eq(
+        "http.response.body.size",
+        trace::HTTP_RESPONSE_BODY_SIZE
+    ));
+}
+
+/// The field key used to record the request/response body size on the span.
+///
+/// Legacy mode keeps the historical `http.request_content_length`/`http.response_content_length`
+/// keys, which predate `opentelemetry_semantic_conventions` and so aren't sourced from it; stable
+/// mode uses the spec's `http.request.body.size`/`http.response.body.size` instead, tied above to
+/// [`trace::HTTP_REQUEST_BODY_SIZE`]/[`trace::HTTP_RESPONSE_BODY_SIZE`].
+#[cfg(not(feature = "semconv_stable_http"))]
+pub(crate) const HTTP_REQUEST_BODY_SIZE: &str = "http.request_content_length";
+#[cfg(feature = "semconv_stable_http")]
+pub(crate) const HTTP_REQUEST_BODY_SIZE: &str = stable::HTTP_REQUEST_BODY_SIZE;
+
+#[cfg(not(feature = "semconv_stable_http"))]
+pub(crate) const HTTP_RESPONSE_BODY_SIZE: &str = "http.response_content_length";
+#[cfg(feature = "semconv_stable_http")]
+pub(crate) const HTTP_RESPONSE_BODY_SIZE: &str = stable::HTTP_RESPONSE_BODY_SIZE;
@@ -0,0 +1,85 @@
+//! Selects the `opentelemetry` / `tracing-opentelemetry` crate pair to build against.
+//!
+//! This relies on `Cargo.toml` renaming each supported release of `opentelemetry`,
+//! `opentelemetry-http`, and `tracing-opentelemetry` under the names imported below, and
+//! gating each rename behind the matching feature (plus a `bytes` dependency for
+//! [`super::OtelOnBodyChunk`]'s `Buf` bound). This checkout doesn't carry a `Cargo.toml` for
+//! `axum-extra` to add those entries to; the manifest is expected to declare, alongside the
+//! existing `[dependencies]` and `[features]`:
+//!
+//! ```toml
+//! [dependencies]
+//! opentelemetry_0_19 = { package = "opentelemetry", version = "0.19", optional = true }
+//! opentelemetry_http_0_19 = { package = "opentelemetry-http", version = "0.8", optional = true }
+//! tracing_opentelemetry_0_19 = { package = "tracing-opentelemetry", version = "0.19", optional = true }
+//! opentelemetry_0_20 = { package = "opentelemetry", version = "0.20", optional = true }
+//! opentelemetry_http_0_20 = { package = "opentelemetry-http", version = "0.9", optional = true }
+//! tracing_opentelemetry_0_20 = { package = "tracing-opentelemetry", version = "0.21", optional = true }
+//! opentelemetry_0_21 = { package = "opentelemetry", version = "0.21", optional = true }
+//! opentelemetry_http_0_21 = { package = "opentelemetry-http", version = "0.10", optional = true }
+//! tracing_opentelemetry_0_21 = { package = "tracing-opentelemetry", version = "0.22", optional = true }
+//! opentelemetry-semantic-conventions = "0.13"
+//! bytes = "1"
+//!
+//! [features]
+//! opentelemetry_0_19 = ["dep:opentelemetry_0_19", "dep:opentelemetry_http_0_19", "dep:tracing_opentelemetry_0_19"]
+//! opentelemetry_0_20 = ["dep:opentelemetry_0_20", "dep:opentelemetry_http_0_20", "dep:tracing_opentelemetry_0_20"]
+//! opentelemetry_0_21 = ["dep:opentelemetry_0_21", "dep:opentelemetry_http_0_21", "dep:tracing_opentelemetry_0_21"]
+//! semconv_stable_http = []
+//! ```
+//!
+//! Until those entries exist, the `compile_error!` below fires for every feature selection and
+//! `axum_extra::middleware::opentelemetry` can't build.
+//!
+//! Downstream users are often already pinned to a particular OpenTelemetry SDK release, so
+//! rather than hard-coding a single version this module re-exports whichever pair was renamed
+//! into scope by the active Cargo feature, the same trick `reqwest-tracing`/`tracing-awc` use
+//! to support several OTel releases side by side. Everything else in [`super`] is written
+//! against these aliases instead of importing `opentelemetry`/`tracing_opentelemetry` directly,
+//! so it keeps compiling unchanged as new versions are added here.
+
+//! `opentelemetry-http` (the `HeaderExtractor`/`HeaderInjector` glue used to bridge
+//! `http::HeaderMap` into OTel's `Extractor`/`Injector` traits) is itself pinned to one
+//! `opentelemetry` release, so it has to be re-aliased here right alongside `otel` — an
+//! un-gated `opentelemetry_http` dependency would only type-check against whichever OTel
+//! version it happens to be pinned to, breaking the other feature selections.
+
+#[cfg(feature = "opentelemetry_0_19")]
+pub(crate) use opentelemetry_0_19 as otel;
+#[cfg(feature = "opentelemetry_0_19")]
+pub(crate) use opentelemetry_http_0_19 as otel_http;
+#[cfg(feature = "opentelemetry_0_19")]
+pub(crate) use tracing_opentelemetry_0_19 as tracing_otel;
+
+#[cfg(all(feature = "opentelemetry_0_20", not(feature = "opentelemetry_0_19")))]
+pub(crate) use opentelemetry_0_20 as otel;
+#[cfg(all(feature = "opentelemetry_0_20", not(feature = "opentelemetry_0_19")))]
+pub(crate) use opentelemetry_http_0_20 as otel_http;
+#[cfg(all(feature = "opentelemetry_0_20", not(feature = "opentelemetry_0_19")))]
+pub(crate) use tracing_opentelemetry_0_20 as tracing_otel;
+
+#[cfg(all(
+    feature = "opentelemetry_0_21",
+    not(any(feature = "opentelemetry_0_19", feature = "opentelemetry_0_20"))
+))]
+pub(crate) use opentelemetry_0_21 as otel;
+#[cfg(all(
+    feature = "opentelemetry_0_21",
+    not(any(feature = "opentelemetry_0_19", feature = "opentelemetry_0_20"))
+))]
+pub(crate) use opentelemetry_http_0_21 as otel_http;
+#[cfg(all(
+    feature = "opentelemetry_0_21",
+    not(any(feature = "opentelemetry_0_19", feature = "opentelemetry_0_20"))
+))]
+pub(crate) use tracing_opentelemetry_0_21 as tracing_otel;
+
+#[cfg(not(any(
+    feature = "opentelemetry_0_19",
+    feature = "opentelemetry_0_20",
+    feature = "opentelemetry_0_21"
+)))]
+compile_error!(
+    "enable exactly one of the `opentelemetry_0_19`, `opentelemetry_0_20`, or \
+     `opentelemetry_0_21` features to use `axum_extra::middleware::opentelemetry`"
+);